@@ -5,8 +5,24 @@
 //! human‑readable output.  See the crate‐level README for background and
 //! motivation.
 
-use std::fs::File;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
 use std::io::{self, Read};
+use std::path::Path;
+
+use serde::Serialize;
+
+#[cfg(feature = "serve")]
+pub mod serve;
+
+/// Schema version for [`generate_json`]'s output, bumped whenever the
+/// shape of the emitted JSON changes in a way consumers should know about.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// Default address the `--serve` preview server binds to when none is
+/// given. Defined here (rather than in [`serve`]) so `main.rs` can use it
+/// without depending on the `serve` feature being enabled.
+pub const DEFAULT_SERVE_ADDR: &str = "127.0.0.1:8046";
 
 /// Represents a documented module.
 ///
@@ -15,16 +31,19 @@ use std::io::{self, Read};
 /// comments `/*! ... */`).  Each entry in `items` represents a top‑level
 /// declaration (forge, shape, process, etc.) along with its signature
 /// and associated doc comments.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DocModule {
     /// Module‑level documentation extracted from `//!` and `/*!` comments.
     pub module_docs: Vec<String>,
     /// Documented items within the module.
     pub items: Vec<DocItem>,
+    /// Frontmatter metadata (e.g. `title`, `author`, `version`) parsed
+    /// from a leading `+++ ... +++` TOML block, if the file has one.
+    pub meta: BTreeMap<String, String>,
 }
 
 /// A single documented top‑level item.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DocItem {
     /// Kind of the item (e.g., `forge`, `shape`, `process`, etc.).
     pub kind: String,
@@ -38,12 +57,55 @@ pub struct DocItem {
     pub is_unsafe: bool,
 }
 
+/// Prefix identifying an `@include` directive within a doc comment line,
+/// e.g. `/// @include path/to/snippet.md`.
+const INCLUDE_PREFIX: &str = "@include ";
+
 /// Parse a Dust source file and return its documentation module.
+///
+/// Unlike [`parse_str`], this also resolves `@include` directives found
+/// in doc comments, splicing in the referenced file's contents relative
+/// to `path`'s directory.
 pub fn parse_file(path: &str) -> io::Result<DocModule> {
     let mut file = File::open(path)?;
     let mut buf = String::new();
     file.read_to_string(&mut buf)?;
-    Ok(parse_str(&buf))
+    let mut module = parse_str(&buf);
+    let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new("."));
+    expand_includes(&mut module.module_docs, base_dir)?;
+    for item in &mut module.items {
+        expand_includes(&mut item.docs, base_dir)?;
+    }
+    Ok(module)
+}
+
+/// Expand `@include path/to/file` directives within `docs` in place,
+/// splicing in the referenced file's lines. Paths are resolved relative
+/// to `base_dir` (the including source file's directory). A missing
+/// file produces an `io::Error` naming the offending path.
+fn expand_includes(docs: &mut Vec<String>, base_dir: &Path) -> io::Result<()> {
+    let mut i = 0;
+    while i < docs.len() {
+        let trimmed = docs[i].trim();
+        let Some(rel_path) = trimmed.strip_prefix(INCLUDE_PREFIX) else {
+            i += 1;
+            continue;
+        };
+        let include_path = base_dir.join(rel_path.trim());
+        let mut file = File::open(&include_path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("failed to resolve @include {}: {}", include_path.display(), e),
+            )
+        })?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        let lines: Vec<String> = contents.lines().map(str::to_string).collect();
+        let line_count = lines.len();
+        docs.splice(i..i + 1, lines);
+        i += line_count;
+    }
+    Ok(())
 }
 
 /// Parse the contents of a Dust source string into a `DocModule`.
@@ -55,8 +117,17 @@ pub fn parse_file(path: &str) -> io::Result<DocModule> {
 /// perform full syntactic analysis of Dust code; instead, it uses simple
 /// heuristics to recognise top‑level declarations (`forge`, `shape`,
 /// `process`, `bind`, `effect`) and treats the next non‑comment line
-/// following a doc comment as that item’s signature.
+/// following a doc comment as that item’s signature. `@include` directives
+/// (see [`parse_file`]) are left as literal text, since resolving them
+/// requires knowing the source file's path.
+///
+/// If the file begins with a `+++ ... +++` TOML frontmatter block (see
+/// [`DocModule::meta`]), it is parsed and stripped before comment
+/// scanning starts.
 pub fn parse_str(src: &str) -> DocModule {
+    let (meta, src) = extract_frontmatter(src);
+    let src = src.as_str();
+
     let mut module_docs: Vec<String> = Vec::new();
     let mut items: Vec<DocItem> = Vec::new();
 
@@ -231,19 +302,237 @@ pub fn parse_str(src: &str) -> DocModule {
         module_docs.extend(current_docs.clone());
         current_docs.clear();
     }
-    DocModule { module_docs, items }
+    DocModule {
+        module_docs,
+        items,
+        meta,
+    }
+}
+
+/// The fence marking the start and end of a TOML frontmatter block.
+const FRONTMATTER_FENCE: &str = "+++";
+
+/// Strip a leading `+++ ... +++` TOML frontmatter block from `src`, if
+/// present, returning its parsed key/value pairs and the remaining
+/// source. The block is only recognised when it is the very first
+/// non‑empty content of the file; a `+++` found anywhere else is left
+/// alone for the normal comment scanner to ignore.
+fn extract_frontmatter(src: &str) -> (BTreeMap<String, String>, String) {
+    let all_lines: Vec<&str> = src.lines().collect();
+    let mut start = 0;
+    while start < all_lines.len() && all_lines[start].trim().is_empty() {
+        start += 1;
+    }
+    if start >= all_lines.len() || all_lines[start].trim() != FRONTMATTER_FENCE {
+        return (BTreeMap::new(), src.to_string());
+    }
+    let close = all_lines[start + 1..]
+        .iter()
+        .position(|line| line.trim() == FRONTMATTER_FENCE)
+        .map(|offset| start + 1 + offset);
+    let Some(close) = close else {
+        return (BTreeMap::new(), src.to_string());
+    };
+    let body = all_lines[start + 1..close].join("\n");
+    let meta = parse_toml_meta(&body);
+    let rest = all_lines[close + 1..].join("\n");
+    (meta, rest)
+}
+
+/// Parse a TOML frontmatter body into a flat string map. Strings are
+/// kept as-is; every other value (numbers, booleans, arrays, tables,
+/// ...) is rendered via its `Display` implementation so no value is
+/// dropped for its type. A body that is not valid TOML yields an empty
+/// map, with no error reported.
+fn parse_toml_meta(body: &str) -> BTreeMap<String, String> {
+    let mut meta = BTreeMap::new();
+    let Ok(table) = body.parse::<toml::Table>() else {
+        return meta;
+    };
+    for (key, value) in table {
+        let rendered = match &value {
+            toml::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        meta.insert(key, rendered);
+    }
+    meta
+}
+
+/// Recursively discover `.dust`/`.dpaper` files under `dir` and parse
+/// each into a `DocModule`, keyed by its path relative to `dir`.
+///
+/// This turns `dustdoc` from a single-file tool into a project
+/// documentation generator: pair it with [`DocIndex::from_modules`] to
+/// build a project-wide index over the result.
+pub fn parse_dir(dir: &str) -> io::Result<Vec<(String, DocModule)>> {
+    let root = Path::new(dir);
+    let mut modules = Vec::new();
+    collect_modules(root, root, &mut modules)?;
+    modules.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(modules)
+}
+
+fn collect_modules(
+    root: &Path,
+    dir: &Path,
+    out: &mut Vec<(String, DocModule)>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_modules(root, &path, out)?;
+            continue;
+        }
+        let is_source = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext == "dust" || ext == "dpaper")
+            .unwrap_or(false);
+        if !is_source {
+            continue;
+        }
+        let module = parse_file(&path.to_string_lossy())?;
+        let rel_name = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .into_owned();
+        out.push((rel_name, module));
+    }
+    Ok(())
+}
+
+/// A project-wide index over the modules discovered by [`parse_dir`],
+/// shared by both the Markdown and HTML index renderers so cross-module
+/// linking stays consistent between them.
+#[derive(Debug, Clone, Serialize)]
+pub struct DocIndex {
+    /// One entry per documented module, in the order `parse_dir` found them.
+    pub entries: Vec<IndexEntry>,
+}
+
+/// A single module's entry in a [`DocIndex`].
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexEntry {
+    /// The module's source path, relative to the documented directory.
+    pub name: String,
+    /// Number of documented items in that module.
+    pub item_count: usize,
+}
+
+impl DocIndex {
+    /// Build an index from the modules returned by [`parse_dir`].
+    pub fn from_modules(modules: &[(String, DocModule)]) -> Self {
+        let entries = modules
+            .iter()
+            .map(|(name, module)| IndexEntry {
+                name: name.clone(),
+                item_count: module.items.len(),
+            })
+            .collect();
+        DocIndex { entries }
+    }
+}
+
+/// Derive a rendered output file's name from a module's source path by
+/// swapping its extension, e.g. `sub/foo.dust` with extension `html`
+/// becomes `sub/foo.html`.
+pub fn output_file_name(source_name: &str, extension: &str) -> String {
+    let path = Path::new(source_name).with_extension(extension);
+    path.to_string_lossy().into_owned()
+}
+
+/// Generate a Markdown index page linking to each module's rendered
+/// output file (named via [`output_file_name`] with `extension`, e.g.
+/// `md` or `html`), annotated with each module's item count.
+///
+/// [`markdown_to_html`] can render the same page as HTML, so both
+/// formats link to the same per-module files built from this index.
+pub fn generate_markdown_index(index: &DocIndex, extension: &str) -> String {
+    let mut out = String::new();
+    out.push_str("# Documentation Index\n\n");
+    for entry in &index.entries {
+        let link = output_file_name(&entry.name, extension);
+        let plural = if entry.item_count == 1 { "" } else { "s" };
+        out.push_str(&format!(
+            "- [{}]({}) — {} item{}\n",
+            entry.name, link, entry.item_count, plural
+        ));
+    }
+    out
+}
+
+/// Language annotations that should be canonicalised to the `dpl` fence
+/// tag by [`process_docs`].
+const DPL_FENCE_ANNOTATIONS: [&str; 4] = ["dpl", "no_run", "ignore", "should_panic"];
+
+/// Normalize fenced code blocks within a set of doc comment lines.
+///
+/// This rewrites bare ```` ``` ```` fences and dpl-family annotations
+/// (`no_run`, `ignore`, `should_panic`) into a canonical ```` ```dpl ````
+/// language tag for syntax highlighting, and strips lines beginning with
+/// a single `#` inside code fences so authors can include hidden
+/// setup/boilerplate lines that compile but don't clutter rendered
+/// output. A line beginning with `##` renders as a single literal `#`.
+pub fn process_docs(docs: &[String]) -> String {
+    let mut out = String::new();
+    let mut in_codeblock = false;
+    for line in docs {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            if !in_codeblock {
+                let lang = trimmed.trim_start_matches("```").trim();
+                if lang.is_empty() || DPL_FENCE_ANNOTATIONS.contains(&lang) {
+                    out.push_str("```dpl");
+                } else {
+                    out.push_str(line);
+                }
+            } else {
+                out.push_str("```");
+            }
+            in_codeblock = !in_codeblock;
+            out.push('\n');
+            continue;
+        }
+        if in_codeblock {
+            if let Some(rest) = trimmed.strip_prefix("##") {
+                out.push('#');
+                out.push_str(rest);
+                out.push('\n');
+                continue;
+            }
+            if trimmed.starts_with('#') {
+                // hidden setup/boilerplate line
+                continue;
+            }
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
 }
 
 /// Generate a Markdown document from a parsed `DocModule`.
+///
+/// The heading uses the frontmatter `title` (see [`DocModule::meta`]) if
+/// present, falling back to `file_name`; any remaining metadata keys are
+/// rendered as a table beneath it.
 pub fn generate_markdown(module: &DocModule, file_name: &str) -> String {
     let mut out = String::new();
-    out.push_str(&format!("# Documentation for `{}`\n\n", file_name));
+    let title = module
+        .meta
+        .get("title")
+        .map(String::as_str)
+        .unwrap_or(file_name);
+    out.push_str(&format!(
+        "# Documentation for `{}`\n\n",
+        escape_heading_title(title)
+    ));
+    out.push_str(&meta_table(module));
     // Module docs
     if !module.module_docs.is_empty() {
-        for line in &module.module_docs {
-            out.push_str(line);
-            out.push('\n');
-        }
+        out.push_str(&process_docs(&module.module_docs));
         out.push('\n');
     }
     // Items
@@ -253,10 +542,7 @@ pub fn generate_markdown(module: &DocModule, file_name: &str) -> String {
             "## {} `{}`{}\n\n",
             item.kind, item.name, unsafe_badge
         ));
-        for line in &item.docs {
-            out.push_str(line);
-            out.push('\n');
-        }
+        out.push_str(&process_docs(&item.docs));
         out.push('\n');
         out.push_str("```dpl\n");
         out.push_str(&item.signature.trim());
@@ -278,11 +564,202 @@ pub fn generate_markdown(module: &DocModule, file_name: &str) -> String {
     out
 }
 
-/// Convert Markdown into HTML using `pulldown-cmark`.
+/// Escape a metadata value for interpolation into a Markdown table
+/// cell: `|` would otherwise terminate the cell early, and a literal
+/// newline would break the table row onto multiple lines.
+fn escape_table_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Escape a title for interpolation into the heading's backtick code
+/// span: a literal backtick would terminate the span early, and a
+/// newline would split the `#` heading across multiple lines.
+fn escape_heading_title(value: &str) -> String {
+    value.replace('`', "'").replace('\n', " ")
+}
+
+/// Render a module's frontmatter metadata (excluding `title`, which is
+/// used for the heading instead) as a Markdown table.
+fn meta_table(module: &DocModule) -> String {
+    let mut out = String::new();
+    let rest: Vec<(&String, &String)> = module
+        .meta
+        .iter()
+        .filter(|(key, _)| key.as_str() != "title")
+        .collect();
+    if rest.is_empty() {
+        return out;
+    }
+    out.push_str("| Key | Value |\n| --- | --- |\n");
+    for (key, value) in rest {
+        out.push_str(&format!(
+            "| {} | {} |\n",
+            escape_table_cell(key),
+            escape_table_cell(value)
+        ));
+    }
+    out.push('\n');
+    out
+}
+
+/// Serialize a `DocModule` to a stable JSON representation.
+///
+/// The output is an object with a `schema_version` field (see
+/// [`JSON_SCHEMA_VERSION`]) alongside the module's docs and items, so
+/// downstream tooling (IDE tooltips, search indexes, cross‑project
+/// aggregation) can detect format changes without re-parsing Dust source.
+pub fn generate_json(module: &DocModule) -> serde_json::Result<String> {
+    #[derive(Serialize)]
+    struct JsonDoc<'a> {
+        schema_version: u32,
+        #[serde(flatten)]
+        module: &'a DocModule,
+    }
+    serde_json::to_string_pretty(&JsonDoc {
+        schema_version: JSON_SCHEMA_VERSION,
+        module,
+    })
+}
+
+/// Generate a plaintext rendering of a parsed `DocModule`.
+///
+/// This emits the same signatures and docs as [`generate_markdown`] but
+/// with no fences or markup, which makes it convenient to read in a
+/// terminal or to pipe through `grep`. Doc comments are passed through
+/// [`process_docs`] just like the Markdown renderer, so `#`-prefixed setup
+/// lines stay hidden; the fence lines themselves are then dropped.
+pub fn generate_plaintext(module: &DocModule, file_name: &str) -> String {
+    let plain_docs = |docs: &[String]| -> String {
+        process_docs(docs)
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("```"))
+            .map(|line| format!("{}\n", line))
+            .collect()
+    };
+    let mut out = String::new();
+    let title = module
+        .meta
+        .get("title")
+        .map(String::as_str)
+        .unwrap_or(file_name);
+    out.push_str(&format!("Documentation for {}\n\n", title.replace('\n', " ")));
+    for (key, value) in module.meta.iter().filter(|(key, _)| key.as_str() != "title") {
+        out.push_str(&format!("{}: {}\n", key, value.replace('\n', " ")));
+    }
+    if module.meta.keys().any(|key| key != "title") {
+        out.push('\n');
+    }
+    if !module.module_docs.is_empty() {
+        out.push_str(&plain_docs(&module.module_docs));
+        out.push('\n');
+    }
+    for item in &module.items {
+        let unsafe_badge = if item.is_unsafe { " (unsafe)" } else { "" };
+        out.push_str(&format!("{} {}{}\n", item.kind, item.name, unsafe_badge));
+        out.push_str(&plain_docs(&item.docs));
+        out.push('\n');
+        out.push_str(item.signature.trim());
+        out.push_str("\n\n");
+    }
+    out
+}
+
+/// Convert Markdown into HTML using `pulldown-cmark`, with unique slug
+/// `id` attributes injected into each heading so sections can be deep
+/// linked.
 pub fn markdown_to_html(markdown: &str) -> String {
+    markdown_to_html_with_ids(markdown).0
+}
+
+/// Like [`markdown_to_html`], but also returns the heading text to slug
+/// map used to inject the `id` attributes, so a table of contents can be
+/// built from the same identifiers.
+pub fn markdown_to_html_with_ids(markdown: &str) -> (String, Vec<(String, String)>) {
     use pulldown_cmark::{html::push_html, Options, Parser};
     let parser = Parser::new_ext(markdown, Options::ENABLE_TABLES | Options::ENABLE_FOOTNOTES);
     let mut html = String::new();
     push_html(&mut html, parser);
-    html
+    add_heading_ids(&html)
+}
+
+/// Slugify heading text: lowercase, spaces become `-`, and anything
+/// that isn't alphanumeric, whitespace or `-` is dropped.
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if c.is_whitespace() || c == '-' {
+            slug.push('-');
+        }
+    }
+    slug
+}
+
+/// Strip any `<tag>` markup from `html`, leaving only the visible text.
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Inject `id` attributes into every `<h1>`-`<h6>` heading in `html`,
+/// slugifying the heading's visible text and disambiguating collisions
+/// by appending `-1`, `-2`, etc. Returns the annotated HTML along with a
+/// map from each heading's visible text to the slug it was given.
+fn add_heading_ids(html: &str) -> (String, Vec<(String, String)>) {
+    let mut out = String::with_capacity(html.len());
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    let mut used: HashSet<String> = HashSet::new();
+    let mut slugs = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("<h") {
+        let level_byte = rest.as_bytes().get(start + 2).copied();
+        let Some(level) = level_byte.filter(|b| b.is_ascii_digit()) else {
+            out.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            continue;
+        };
+        let open_tag = format!("<h{}>", level as char);
+        let close_tag = format!("</h{}>", level as char);
+        if !rest[start..].starts_with(&open_tag) {
+            out.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let after_open = &rest[start + open_tag.len()..];
+        let Some(end) = after_open.find(&close_tag) else {
+            out.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let inner = &after_open[..end];
+        let visible = strip_tags(inner);
+        let base = slugify(&visible);
+        let count = counts.entry(base.clone()).or_insert(0);
+        let mut slug = if *count == 0 {
+            base.clone()
+        } else {
+            format!("{}-{}", base, count)
+        };
+        *count += 1;
+        while !used.insert(slug.clone()) {
+            slug = format!("{}-{}", base, count);
+            *count += 1;
+        }
+        out.push_str(&format!("<h{} id=\"{}\">{}</h{}>", level as char, slug, inner, level as char));
+        slugs.push((visible, slug));
+        rest = &after_open[end + close_tag.len()..];
+    }
+    out.push_str(rest);
+    (out, slugs)
 }
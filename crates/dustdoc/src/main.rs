@@ -2,22 +2,198 @@
 //!
 //! This binary reads a Dust source file, extracts documentation
 //! comments, and writes out a formatted document.  By default it
-//! produces Markdown; pass `--html` to emit HTML instead.
+//! produces Markdown; pass `--html`, `--json` or `--txt` to pick another
+//! format explicitly, or let it be inferred from the output file's
+//! extension.
 
 use std::env;
 use std::fs;
 use std::path::Path;
 
-use dustdoc::{generate_markdown, markdown_to_html, parse_file};
+use dustdoc::{
+    generate_json, generate_markdown, generate_markdown_index, generate_plaintext,
+    markdown_to_html, output_file_name, parse_dir, parse_file, DocIndex, DEFAULT_SERVE_ADDR,
+};
+
+#[cfg(feature = "serve")]
+fn run_serve(source: &str, addr: &str) {
+    if let Err(e) = dustdoc::serve::serve(source, addr) {
+        eprintln!("error: failed to serve on {}: {}", addr, e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "serve"))]
+fn run_serve(_source: &str, _addr: &str) {
+    eprintln!("error: dustdoc was built without the `serve` feature");
+    std::process::exit(1);
+}
+
+/// The format `dustdoc` should render its output in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Markdown,
+    Html,
+    Json,
+    Plaintext,
+}
+
+impl OutputFormat {
+    /// Infer a format from an output file's extension, if recognised.
+    fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Some(OutputFormat::Markdown),
+            "html" | "htm" => Some(OutputFormat::Html),
+            "json" => Some(OutputFormat::Json),
+            "txt" => Some(OutputFormat::Plaintext),
+            _ => None,
+        }
+    }
+
+    /// Resolve the format to use, given explicit CLI flags and an
+    /// optional output path.  Explicit flags always win; otherwise the
+    /// output file's extension is consulted; failing that, Markdown is
+    /// the default.
+    fn resolve(flags: &OutputFormatFlags, output: Option<&str>) -> Self {
+        if let Some(format) = flags.explicit() {
+            return format;
+        }
+        output
+            .and_then(|path| Path::new(path).extension())
+            .and_then(|ext| ext.to_str())
+            .and_then(OutputFormat::from_extension)
+            .unwrap_or(OutputFormat::Markdown)
+    }
+}
+
+/// Tracks which `--<format>` flags were passed on the command line.
+#[derive(Debug, Default)]
+struct OutputFormatFlags {
+    html: bool,
+    json: bool,
+    txt: bool,
+}
+
+impl OutputFormatFlags {
+    /// The format requested by an explicit flag, if any, erroring out on
+    /// conflicting flags.
+    fn explicit(&self) -> Option<OutputFormat> {
+        let mut chosen = None;
+        for (set, format) in [
+            (self.html, OutputFormat::Html),
+            (self.json, OutputFormat::Json),
+            (self.txt, OutputFormat::Plaintext),
+        ] {
+            if set {
+                if chosen.is_some() {
+                    eprintln!("error: at most one of --html, --json, --txt may be given");
+                    std::process::exit(1);
+                }
+                chosen = Some(format);
+            }
+        }
+        chosen
+    }
+}
 
 fn print_help() {
     eprintln!("Usage: dustdoc [OPTIONS] <source> [<output>]");
     eprintln!("\nOptions:");
-    eprintln!("    --html       Generate HTML instead of Markdown (default is Markdown).");
-    eprintln!("    -h, --help   Print this help message.");
+    eprintln!("    --html         Generate HTML instead of Markdown.");
+    eprintln!("    --json         Generate structured JSON instead of Markdown.");
+    eprintln!("    --txt          Generate plaintext instead of Markdown.");
+    eprintln!("    --serve[=addr] Serve a live HTML preview (default 127.0.0.1:8046),");
+    eprintln!("                   re-rendering <source> on every request.");
+    eprintln!("    -h, --help     Print this help message.");
     eprintln!("\nArguments:");
-    eprintln!("    <source>     Path to a `.dust` or `.dpaper` source file.");
-    eprintln!("    [output]     Optional output file.  If omitted, the result is printed to stdout.");
+    eprintln!("    <source>     Path to a `.dust`/`.dpaper` source file, or a directory to");
+    eprintln!("                 document recursively.");
+    eprintln!("    [output]     Optional output file (or output directory, when <source> is a");
+    eprintln!("                 directory).  If omitted, a single-file result is printed to");
+    eprintln!("                 stdout and a directory result is written under ./docs.");
+    eprintln!("                 Unless a format flag is given, the format is inferred from the");
+    eprintln!("                 output file's extension (.md/.markdown, .html/.htm, .json,");
+    eprintln!("                 .txt), defaulting to Markdown.");
+}
+
+/// Document every module under the `source` directory, writing one
+/// rendered file per module plus an index page into `output_dir`
+/// (`./docs` if not given).
+fn run_dir_mode(source: &str, output_dir: Option<&str>, flags: &OutputFormatFlags) {
+    let format = flags.explicit().unwrap_or(OutputFormat::Markdown);
+    let out_dir = output_dir.unwrap_or("docs");
+    if let Err(e) = fs::create_dir_all(out_dir) {
+        eprintln!("error: failed to create output directory {}: {}", out_dir, e);
+        std::process::exit(1);
+    }
+    let modules = match parse_dir(source) {
+        Ok(m) => m,
+        Err(e) => {
+            eprintln!("error: failed to parse directory {}: {}", source, e);
+            std::process::exit(1);
+        }
+    };
+    let ext = match format {
+        OutputFormat::Html => "html",
+        OutputFormat::Json => "json",
+        OutputFormat::Plaintext => "txt",
+        OutputFormat::Markdown => "md",
+    };
+    // Written under a reserved name so a module's own rendered output
+    // (e.g. a real `index.dust`) does not collide with it; a module that
+    // still would (e.g. `_index.dust`) is rejected with an error below.
+    let index_md_path = Path::new(out_dir).join("_index.md");
+    let index_html_path = Path::new(out_dir).join("_index.html");
+    for (name, module) in &modules {
+        let rendered = match format {
+            OutputFormat::Json => match generate_json(module) {
+                Ok(text) => text,
+                Err(e) => {
+                    eprintln!("error: failed to serialize {} to JSON: {}", name, e);
+                    std::process::exit(1);
+                }
+            },
+            OutputFormat::Plaintext => generate_plaintext(module, name),
+            OutputFormat::Html => markdown_to_html(&generate_markdown(module, name)),
+            OutputFormat::Markdown => generate_markdown(module, name),
+        };
+        let out_path = Path::new(out_dir).join(output_file_name(name, ext));
+        if out_path == index_md_path || out_path == index_html_path {
+            eprintln!(
+                "error: module {} would overwrite the generated index at {}",
+                name,
+                out_path.display()
+            );
+            std::process::exit(1);
+        }
+        if let Some(parent) = out_path.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                eprintln!("error: failed to create {}: {}", parent.display(), e);
+                std::process::exit(1);
+            }
+        }
+        if let Err(e) = fs::write(&out_path, rendered) {
+            eprintln!("error: failed to write {}: {}", out_path.display(), e);
+            std::process::exit(1);
+        }
+    }
+    let index = DocIndex::from_modules(&modules);
+    let index_md = generate_markdown_index(&index, ext);
+    if let Err(e) = fs::write(&index_md_path, &index_md) {
+        eprintln!("error: failed to write {}: {}", index_md_path.display(), e);
+        std::process::exit(1);
+    }
+    if format == OutputFormat::Html {
+        if let Err(e) = fs::write(&index_html_path, markdown_to_html(&index_md)) {
+            eprintln!("error: failed to write {}: {}", index_html_path.display(), e);
+            std::process::exit(1);
+        }
+    }
+    println!(
+        "dustdoc: documented {} module(s) into {}",
+        modules.len(),
+        out_dir
+    );
 }
 
 fn main() {
@@ -27,14 +203,33 @@ fn main() {
         return;
     }
     // parse flags
-    let mut html = false;
+    let mut flags = OutputFormatFlags::default();
+    let mut serve_addr: Option<String> = None;
     let mut i = 0;
     while i < args.len() {
-        if args[i] == "--html" {
-            html = true;
-            args.remove(i);
-        } else {
-            i += 1;
+        match args[i].as_str() {
+            "--html" => {
+                flags.html = true;
+                args.remove(i);
+            }
+            "--json" => {
+                flags.json = true;
+                args.remove(i);
+            }
+            "--txt" => {
+                flags.txt = true;
+                args.remove(i);
+            }
+            "--serve" => {
+                args.remove(i);
+                serve_addr = Some(DEFAULT_SERVE_ADDR.to_string());
+            }
+            arg if arg.starts_with("--serve=") => {
+                let addr = arg.trim_start_matches("--serve=").to_string();
+                args.remove(i);
+                serve_addr = Some(addr);
+            }
+            _ => i += 1,
         }
     }
     if args.is_empty() {
@@ -42,7 +237,16 @@ fn main() {
         return;
     }
     let source = args[0].clone();
+    if let Some(addr) = serve_addr {
+        run_serve(&source, &addr);
+        return;
+    }
     let output = if args.len() > 1 { Some(args[1].clone()) } else { None };
+    if Path::new(&source).is_dir() {
+        run_dir_mode(&source, output.as_deref(), &flags);
+        return;
+    }
+    let format = OutputFormat::resolve(&flags, output.as_deref());
     // parse file
     let module = match parse_file(&source) {
         Ok(m) => m,
@@ -55,8 +259,18 @@ fn main() {
         .file_name()
         .and_then(|s| s.to_str())
         .unwrap_or(&source);
-    let md = generate_markdown(&module, file_name);
-    let output_text = if html { markdown_to_html(&md) } else { md };
+    let output_text = match format {
+        OutputFormat::Json => match generate_json(&module) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("error: failed to serialize JSON: {}", e);
+                std::process::exit(1);
+            }
+        },
+        OutputFormat::Plaintext => generate_plaintext(&module, file_name),
+        OutputFormat::Html => markdown_to_html(&generate_markdown(&module, file_name)),
+        OutputFormat::Markdown => generate_markdown(&module, file_name),
+    };
     match output {
         Some(ref out_path) => {
             if let Err(e) = fs::write(out_path, output_text) {
@@ -68,4 +282,4 @@ fn main() {
             println!("{}", output_text);
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,39 @@
+//! A tiny embedded HTTP server for live-previewing generated HTML docs.
+//!
+//! Gated behind the `serve` feature so that the core `dustdoc` library
+//! stays dependency-light for consumers that only need parsing and
+//! rendering.
+
+use std::io::Write;
+use std::net::TcpListener;
+
+use crate::{generate_markdown, markdown_to_html, parse_file, DEFAULT_SERVE_ADDR};
+
+/// Default address the preview server binds to when none is given.
+pub const DEFAULT_ADDR: &str = DEFAULT_SERVE_ADDR;
+
+/// Serve a live HTML preview of `source` at `addr`, re-parsing and
+/// re-rendering the file on every request so edits show up on refresh.
+///
+/// This blocks the calling thread, handling one request at a time.
+pub fn serve(source: &str, addr: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    println!("dustdoc: serving {} at http://{}", source, addr);
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(s) => s,
+            Err(_) => continue,
+        };
+        let body = match parse_file(source) {
+            Ok(module) => markdown_to_html(&generate_markdown(&module, source)),
+            Err(e) => format!("<pre>error: failed to read {}: {}</pre>", source, e),
+        };
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let _ = stream.write_all(response.as_bytes());
+    }
+    Ok(())
+}
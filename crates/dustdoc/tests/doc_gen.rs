@@ -1,4 +1,8 @@
-use dustdoc::{generate_markdown, markdown_to_html, parse_str};
+use dustdoc::{
+    generate_json, generate_markdown, generate_markdown_index, generate_plaintext,
+    markdown_to_html, markdown_to_html_with_ids, parse_dir, parse_file, parse_str, process_docs,
+    DocIndex,
+};
 
 #[test]
 fn parse_and_generate_basic() {
@@ -31,8 +35,8 @@ process make_value() -> MyForge {
     assert!(md.contains("```dpl"));
     // convert to html just to ensure the function executes
     let html = markdown_to_html(&md);
-    // Check for actual HTML output format
-    assert!(html.contains("<h2>forge"));
+    // Check for actual HTML output format, with a slug id injected
+    assert!(html.contains("<h2 id=\"forge-myforge\">forge"));
 }
 
 #[test]
@@ -53,3 +57,185 @@ shape Widget {}
     assert_eq!(module.items[0].kind, "shape");
     assert!(module.items[0].docs[0].contains("Multi‑line doc comment"));
 }
+
+#[test]
+fn generate_json_contains_schema_version_and_items() {
+    let src = r#"/// A simple forge.
+forge MyForge {
+    answer: Int,
+}
+"#;
+    let module = parse_str(src);
+    let json = generate_json(&module).expect("serialization should succeed");
+    assert!(json.contains("\"schema_version\": 1"));
+    assert!(json.contains("\"name\": \"MyForge\""));
+    assert!(json.contains("\"kind\": \"forge\""));
+}
+
+#[test]
+fn generate_plaintext_has_no_markup() {
+    let src = r#"/// A simple forge.
+forge MyForge {
+    answer: Int,
+}
+"#;
+    let module = parse_str(src);
+    let text = generate_plaintext(&module, "test.dust");
+    assert!(text.contains("Documentation for test.dust"));
+    assert!(text.contains("forge MyForge"));
+    assert!(!text.contains("```"));
+    assert!(!text.contains('#'));
+}
+
+#[test]
+fn generate_plaintext_hides_setup_lines_in_fenced_docs() {
+    let src = vec![
+        "/// ```no_run".to_string(),
+        "/// # let hidden = setup();".to_string(),
+        "/// visible_line();".to_string(),
+        "/// ```".to_string(),
+    ]
+    .join("\n")
+        + "\nforge MyForge {}\n";
+    let module = parse_str(&src);
+    let text = generate_plaintext(&module, "test.dust");
+    assert!(!text.contains("let hidden = setup();"));
+    assert!(text.contains("visible_line();"));
+}
+
+#[test]
+fn parse_file_expands_include_directives() {
+    let dir = std::env::temp_dir().join("dustdoc_include_test");
+    std::fs::create_dir_all(&dir).unwrap();
+    let snippet_path = dir.join("snippet.md");
+    std::fs::write(&snippet_path, "Spliced line one.\nSpliced line two.\n").unwrap();
+    let source_path = dir.join("source.dust");
+    std::fs::write(
+        &source_path,
+        "/// Intro.\n/// @include snippet.md\nforge MyForge {}\n",
+    )
+    .unwrap();
+
+    let module = parse_file(source_path.to_str().unwrap()).expect("parse_file should succeed");
+    assert_eq!(module.items[0].docs[0], "Intro.");
+    assert_eq!(module.items[0].docs[1], "Spliced line one.");
+    assert_eq!(module.items[0].docs[2], "Spliced line two.");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn parse_str_leaves_include_directives_literal() {
+    let src = "/// @include snippet.md\nforge MyForge {}\n";
+    let module = parse_str(src);
+    assert_eq!(module.items[0].docs[0], "@include snippet.md");
+}
+
+#[test]
+fn parse_str_extracts_toml_frontmatter() {
+    let src = r#"+++
+title = "My Module"
+author = "Jane"
++++
+/// A simple forge.
+forge MyForge {}
+"#;
+    let module = parse_str(src);
+    assert_eq!(module.meta.get("title").map(String::as_str), Some("My Module"));
+    assert_eq!(module.meta.get("author").map(String::as_str), Some("Jane"));
+    assert_eq!(module.items.len(), 1);
+
+    let md = generate_markdown(&module, "test.dust");
+    assert!(md.contains("# Documentation for `My Module`"));
+    assert!(md.contains("| author | Jane |"));
+    assert!(!md.contains("| title |"));
+}
+
+#[test]
+fn generate_markdown_escapes_pipes_in_meta_table() {
+    let src = "+++\ntitle = \"T\"\nnote = \"a | b\"\n+++\nforge MyForge {}\n";
+    let module = parse_str(src);
+    let md = generate_markdown(&module, "test.dust");
+    assert!(md.contains("| note | a \\| b |"));
+    // A naively-interpolated pipe would split the row into extra cells;
+    // make sure the table still has exactly the two columns we wrote.
+    let row = md.lines().find(|l| l.starts_with("| note")).unwrap();
+    assert_eq!(row.replace("\\|", "").matches('|').count(), 3);
+}
+
+#[test]
+fn generate_markdown_escapes_backtick_and_newline_in_title() {
+    let src = "+++\ntitle = \"Weird `Title`\"\n+++\nforge MyForge {}\n";
+    let module = parse_str(src);
+    let md = generate_markdown(&module, "test.dust");
+    // A literal backtick would close the code span early; the escaped
+    // title should stay inside a single pair of backticks.
+    assert!(md.contains("# Documentation for `Weird 'Title'`"));
+
+    let src = "+++\ntitle = \"Evil\\nTitle\"\n+++\nforge MyForge {}\n";
+    let module = parse_str(src);
+    let md = generate_markdown(&module, "test.dust");
+    let text = generate_plaintext(&module, "test.dust");
+    assert!(md.contains("# Documentation for `Evil Title`"));
+    assert!(text.contains("Documentation for Evil Title\n"));
+}
+
+#[test]
+fn parse_str_ignores_non_leading_plus_fence() {
+    let src = "/// A doc.\nforge MyForge {}\n+++\nnot frontmatter\n+++\n";
+    let module = parse_str(src);
+    assert!(module.meta.is_empty());
+}
+
+#[test]
+fn parse_dir_discovers_modules_and_builds_index() {
+    let dir = std::env::temp_dir().join("dustdoc_parse_dir_test");
+    std::fs::create_dir_all(dir.join("nested")).unwrap();
+    std::fs::write(dir.join("a.dust"), "/// A.\nforge A {}\n").unwrap();
+    std::fs::write(dir.join("nested").join("b.dpaper"), "/// B.\nforge B {}\n").unwrap();
+    std::fs::write(dir.join("notes.txt"), "not a source file").unwrap();
+
+    let modules = parse_dir(dir.to_str().unwrap()).expect("parse_dir should succeed");
+    assert_eq!(modules.len(), 2);
+    let names: Vec<&str> = modules.iter().map(|(name, _)| name.as_str()).collect();
+    assert!(names.contains(&"a.dust"));
+    assert!(names.iter().any(|n| n.ends_with("b.dpaper")));
+
+    let index = DocIndex::from_modules(&modules);
+    let index_md = generate_markdown_index(&index, "html");
+    assert!(index_md.contains("a.html"));
+    assert!(index_md.contains("1 item"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn markdown_to_html_dedupes_heading_ids() {
+    let md = "## Widget\n\ntext\n\n## Widget\n";
+    let (html, slugs) = markdown_to_html_with_ids(md);
+    assert!(html.contains("<h2 id=\"widget\">Widget</h2>"));
+    assert!(html.contains("<h2 id=\"widget-1\">Widget</h2>"));
+    assert_eq!(
+        slugs,
+        vec![
+            ("Widget".to_string(), "widget".to_string()),
+            ("Widget".to_string(), "widget-1".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn process_docs_normalizes_fences_and_hides_setup_lines() {
+    let docs: Vec<String> = vec![
+        "```no_run".to_string(),
+        "# let hidden = setup();".to_string(),
+        "## literal_hash_line();".to_string(),
+        "visible_line();".to_string(),
+        "```".to_string(),
+    ];
+    let processed = process_docs(&docs);
+    assert!(processed.contains("```dpl"));
+    assert!(!processed.contains("let hidden = setup();"));
+    assert!(processed.contains("# literal_hash_line();"));
+    assert!(processed.contains("visible_line();"));
+}